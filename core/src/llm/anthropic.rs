@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{ChatMessage, LlmProvider, PlanOutput, ToolCall};
+use crate::registry::ToolRecord;
+
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+// Talks to Claude's Messages API. Claude's tool_use/tool_result content blocks are
+// normalized into the same `PlanOutput`/`ToolCall` shape the OpenAI backend produces, so
+// the agent loop never has to know which provider it's talking to.
+pub struct AnthropicProvider {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+}
+
+impl AnthropicProvider {
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("ANTHROPIC_API_KEY")
+            .context("ANTHROPIC_API_KEY must be set in .env")?;
+        let model = std::env::var("ANTHROPIC_MODEL")
+            .unwrap_or_else(|_| "claude-3-5-sonnet-latest".to_string());
+        let base_url = std::env::var("ANTHROPIC_API_BASE")
+            .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+
+        Ok(Self { http: reqwest::Client::new(), api_key, base_url, model })
+    }
+
+    fn build_tool_menu(tools: &[ToolRecord]) -> Vec<Value> {
+        tools.iter().map(|t| json!({
+            "name": t.name,
+            "description": t.description,
+            "input_schema": t.parameters,
+        })).collect()
+    }
+
+    // Translates our provider-agnostic history into Claude's message/content-block shape.
+    fn build_messages(history: &[ChatMessage]) -> Vec<Value> {
+        history.iter().map(|msg| match msg {
+            ChatMessage::User(content) => json!({ "role": "user", "content": content }),
+            ChatMessage::Assistant { content, tool_calls } => {
+                let mut blocks = Vec::new();
+                if let Some(text) = content {
+                    blocks.push(json!({ "type": "text", "text": text }));
+                }
+                for call in tool_calls {
+                    let input: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.name,
+                        "input": input,
+                    }));
+                }
+                json!({ "role": "assistant", "content": blocks })
+            }
+            ChatMessage::Tool { tool_call_id, content } => json!({
+                "role": "user",
+                "content": [{
+                    "type": "tool_result",
+                    "tool_use_id": tool_call_id,
+                    "content": content,
+                }],
+            }),
+        }).collect()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn plan(&self, tools: &[ToolRecord], history: &[ChatMessage]) -> Result<PlanOutput> {
+        let body = json!({
+            "model": self.model,
+            "max_tokens": 4096,
+            "messages": Self::build_messages(history),
+            "tools": Self::build_tool_menu(tools),
+        });
+
+        let response = self.http
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+            .send()
+            .await
+            .context("Anthropic request failed")?
+            .error_for_status()
+            .context("Anthropic returned an error response")?
+            .json::<Value>()
+            .await
+            .context("Failed to parse Anthropic response")?;
+
+        let blocks = response["content"].as_array().cloned().unwrap_or_default();
+
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block["type"].as_str() {
+                Some("text") => content.push_str(block["text"].as_str().unwrap_or_default()),
+                Some("tool_use") => tool_calls.push(ToolCall {
+                    id: block["id"].as_str().unwrap_or_default().to_string(),
+                    name: block["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: block["input"].to_string(),
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(PlanOutput {
+            content: if content.is_empty() { None } else { Some(content) },
+            tool_calls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::registry::ToolLimits;
+
+    fn make_tool(name: &str) -> ToolRecord {
+        ToolRecord {
+            name: name.to_string(),
+            description: format!("does {}", name),
+            binary_path: "tools/whatever.wasm".to_string(),
+            handler: "run".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+            capabilities: Default::default(),
+            limits: ToolLimits::default(),
+            sha256: None,
+        }
+    }
+
+    // A one-shot HTTP server that ignores whatever it's sent and always replies with the
+    // given JSON body - same technique `core/src/registry.rs`'s tests use to exercise
+    // network-dependent code without a real network dependency.
+    fn spawn_static_json_server(body: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(body.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn make_provider(base_url: String) -> AnthropicProvider {
+        AnthropicProvider {
+            http: reqwest::Client::new(),
+            api_key: "test-key".to_string(),
+            base_url,
+            model: "claude-3-5-sonnet-latest".to_string(),
+        }
+    }
+
+    #[test]
+    fn build_tool_menu_maps_to_claudes_input_schema_shape() {
+        let tools = vec![make_tool("search")];
+        let menu = AnthropicProvider::build_tool_menu(&tools);
+
+        assert_eq!(menu.len(), 1);
+        assert_eq!(menu[0]["name"], "search");
+        assert_eq!(menu[0]["description"], "does search");
+        assert_eq!(menu[0]["input_schema"], tools[0].parameters);
+    }
+
+    #[test]
+    fn build_messages_translates_tool_calls_and_results_into_content_blocks() {
+        let history = vec![
+            ChatMessage::User("what's the weather?".to_string()),
+            ChatMessage::Assistant {
+                content: Some("let me check".to_string()),
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "weather".to_string(),
+                    arguments: r#"{"city":"nyc"}"#.to_string(),
+                }],
+            },
+            ChatMessage::Tool { tool_call_id: "call-1".to_string(), content: "72F".to_string() },
+        ];
+
+        let messages = AnthropicProvider::build_messages(&history);
+
+        assert_eq!(messages[0], json!({ "role": "user", "content": "what's the weather?" }));
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"][0], json!({ "type": "text", "text": "let me check" }));
+        assert_eq!(messages[1]["content"][1], json!({
+            "type": "tool_use",
+            "id": "call-1",
+            "name": "weather",
+            "input": { "city": "nyc" },
+        }));
+        assert_eq!(messages[2], json!({
+            "role": "user",
+            "content": [{ "type": "tool_result", "tool_use_id": "call-1", "content": "72F" }],
+        }));
+    }
+
+    #[tokio::test]
+    async fn plan_parses_text_and_tool_use_blocks_into_plan_output() {
+        let body = r#"{
+            "content": [
+                { "type": "text", "text": "checking the weather" },
+                { "type": "tool_use", "id": "toolu_01", "name": "weather", "input": { "city": "nyc" } }
+            ]
+        }"#;
+        let base_url = spawn_static_json_server(body);
+        let provider = make_provider(base_url);
+
+        let output = provider.plan(&[], &[ChatMessage::User("what's the weather?".to_string())]).await.unwrap();
+
+        assert_eq!(output.content, Some("checking the weather".to_string()));
+        assert_eq!(output.tool_calls.len(), 1);
+        assert_eq!(output.tool_calls[0].id, "toolu_01");
+        assert_eq!(output.tool_calls[0].name, "weather");
+        assert_eq!(
+            serde_json::from_str::<Value>(&output.tool_calls[0].arguments).unwrap(),
+            json!({ "city": "nyc" }),
+        );
+    }
+
+    #[tokio::test]
+    async fn plan_returns_no_content_when_claude_replies_with_only_tool_use() {
+        let body = r#"{ "content": [{ "type": "tool_use", "id": "toolu_02", "name": "weather", "input": {} }] }"#;
+        let base_url = spawn_static_json_server(body);
+        let provider = make_provider(base_url);
+
+        let output = provider.plan(&[], &[ChatMessage::User("go".to_string())]).await.unwrap();
+
+        assert_eq!(output.content, None);
+        assert_eq!(output.tool_calls.len(), 1);
+    }
+}
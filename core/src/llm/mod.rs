@@ -0,0 +1,356 @@
+mod anthropic;
+mod ollama;
+mod openai;
+
+pub use anthropic::AnthropicProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::info;
+
+// Import our internal Tool Definition
+use crate::registry::{Registry, ToolRecord};
+use crate::runtime::Runtime;
+
+// Diagnostics captured from one tool execution during an agent run: what it printed,
+// how much fuel it burned, and whether it trapped. Surfaced alongside the final answer so
+// callers aren't limited to just the text the model produced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallDiagnostics {
+    pub tool_name: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub fuel_consumed: u64,
+    pub trapped: bool,
+}
+
+// The outcome of a full agent run: the model's final answer plus diagnostics for every
+// tool call made along the way.
+#[derive(Debug, Clone)]
+pub struct AgentRun {
+    pub answer: String,
+    pub diagnostics: Vec<ToolCallDiagnostics>,
+    // How many plan/execute turns the loop took before reaching a final answer. Useful for
+    // benchmarking: a task a model can answer in one step is cheaper than one needing five.
+    pub steps: usize,
+}
+
+// Hard ceiling on how many turns the agent loop will take before giving up.
+// Stops a model stuck alternating tool_calls forever from running the gateway out of fuel.
+const MAX_AGENT_STEPS: usize = 8;
+
+// Distinguishes hitting the step ceiling from any other failure, so callers can still get
+// at whatever diagnostics the run accumulated before giving up instead of just an error
+// string - precisely the run where knowing what actually executed matters most.
+#[derive(Debug, Error)]
+pub enum AgentError {
+    #[error("agent exceeded max step limit ({steps}) without reaching a final answer")]
+    StepLimitExceeded {
+        diagnostics: Vec<ToolCallDiagnostics>,
+        steps: usize,
+    },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+// One turn in the conversation we replay to the model every step of the agent loop.
+// Provider-agnostic so the same history can be translated into OpenAI's, Anthropic's and
+// Ollama's own wire formats.
+#[derive(Debug, Clone)]
+pub enum ChatMessage {
+    User(String),
+    Assistant { content: Option<String>, tool_calls: Vec<ToolCall> },
+    Tool { tool_call_id: String, content: String },
+}
+
+// A tool invocation the model asked for, normalized to the common
+// `{ "function": { "name", "arguments" } }` shape regardless of which provider emitted it.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String, // JSON-encoded arguments object
+}
+
+// What a provider hands back for a single turn: either a final answer (`content`) or one
+// or more tool calls that need to be executed before asking the model again.
+#[derive(Debug, Clone, Default)]
+pub struct PlanOutput {
+    pub content: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+// Implemented once per backend (OpenAI, Anthropic, Ollama, ...). Each implementation owns
+// translating our `ToolRecord`/`ChatMessage` types into its own wire format and normalizing
+// whatever it gets back (OpenAI tool_calls, Claude tool_use blocks, Ollama's function
+// format) into a `PlanOutput`.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    async fn plan(&self, tools: &[ToolRecord], history: &[ChatMessage]) -> Result<PlanOutput>;
+}
+
+pub struct Brain {
+    provider: Box<dyn LlmProvider>,
+}
+
+impl Brain {
+    pub fn new() -> Result<Self> {
+        dotenvy::dotenv().ok();
+
+        let provider_name = std::env::var("IRONCLAW_PROVIDER")
+            .unwrap_or_else(|_| "openai".to_string());
+
+        let provider: Box<dyn LlmProvider> = match provider_name.as_str() {
+            "openai" => Box::new(OpenAiProvider::from_env()?),
+            "anthropic" => Box::new(AnthropicProvider::from_env()?),
+            "ollama" => Box::new(OllamaProvider::from_env()?),
+            other => anyhow::bail!(
+                "Unknown IRONCLAW_PROVIDER '{}': expected one of openai|anthropic|ollama",
+                other
+            ),
+        };
+
+        info!("Brain connected. Provider: {}", provider_name);
+        Ok(Self { provider })
+    }
+
+    pub async fn say_hello(&self) -> Result<String> {
+        let history = [ChatMessage::User("Hello! Reply with 'System Online'.".to_string())];
+        let output = self.provider.plan(&[], &history).await?;
+        Ok(output.content.unwrap_or_default())
+    }
+
+    // --- The Agent Loop ---
+    // Runs a full multi-step tool-calling conversation instead of a single plan/execute
+    // round trip. Keeps a growing message history, sends it back to the provider each turn,
+    // and executes every requested tool call against the Runtime/Registry before asking
+    // again. Stops once the provider replies with no tool calls left, which is treated as
+    // the final answer.
+    pub async fn run_agent(
+        &self,
+        task: &str,
+        registry: &Registry,
+        runtime: &Runtime,
+    ) -> Result<AgentRun, AgentError> {
+        let mut history = vec![ChatMessage::User(task.to_string())];
+        let mut diagnostics = Vec::new();
+
+        for step in 0..MAX_AGENT_STEPS {
+            let output = self.provider.plan(registry.tools(), &history).await?;
+
+            if output.tool_calls.is_empty() {
+                // No tool calls: the model is done reasoning, this is the final answer.
+                return Ok(AgentRun {
+                    answer: output.content.unwrap_or_default(),
+                    diagnostics,
+                    steps: step + 1,
+                });
+            }
+
+            info!("Agent step {}: model requested {} tool call(s)", step, output.tool_calls.len());
+
+            // Record the assistant's turn (including its tool_calls) before answering them.
+            history.push(ChatMessage::Assistant {
+                content: output.content.clone(),
+                tool_calls: output.tool_calls.clone(),
+            });
+
+            // Dispatch every requested call concurrently rather than one at a time, so a
+            // batch like "weather in London and Paris" runs both lookups in parallel. Each
+            // call resolves to a String regardless of success or failure (errors are turned
+            // into a readable message) so one failing call never aborts the rest of the batch.
+            let outcomes = join_all(
+                output.tool_calls.iter().map(|call| Self::execute_tool_call(call, registry, runtime)),
+            )
+            .await;
+
+            // Feed each result back tagged with its own tool_call_id, so the model can tell
+            // which output answers which call, and keep the run's diagnostics alongside it.
+            for (call, outcome) in output.tool_calls.iter().zip(outcomes) {
+                if let Some(d) = outcome.diagnostics {
+                    diagnostics.push(d);
+                }
+                history.push(ChatMessage::Tool {
+                    tool_call_id: call.id.clone(),
+                    content: outcome.content,
+                });
+            }
+        }
+
+        Err(AgentError::StepLimitExceeded {
+            diagnostics,
+            steps: MAX_AGENT_STEPS,
+        })
+    }
+
+    // Runs a single tool call against the Registry/Runtime. Returns the text the model
+    // should see (the tool's result, or a readable error) plus diagnostics when the call
+    // actually reached the Runtime.
+    async fn execute_tool_call(call: &ToolCall, registry: &Registry, runtime: &Runtime) -> ToolCallOutcome {
+        let tool_record = match registry.tools().iter().find(|t| t.name == call.name) {
+            Some(t) => t,
+            None => {
+                return ToolCallOutcome {
+                    content: format!("Error: Tool '{}' not found in registry", call.name),
+                    diagnostics: None,
+                }
+            }
+        };
+
+        let binary_path = match registry.resolve_binary(tool_record).await {
+            Ok(p) => p,
+            Err(e) => {
+                return ToolCallOutcome {
+                    content: format!("Error: tool '{}' binary could not be resolved: {}", call.name, e),
+                    diagnostics: None,
+                }
+            }
+        };
+
+        let args: Value = serde_json::from_str(&call.arguments).unwrap_or_default();
+        let input = args["input"].as_str().unwrap_or("").to_string();
+
+        match runtime.run_tool(tool_record, &binary_path, input).await {
+            Ok(exec) => {
+                let content = if exec.trapped {
+                    format!("Error: tool '{}' trapped: {}", call.name, exec.result)
+                } else {
+                    exec.result.clone()
+                };
+                ToolCallOutcome {
+                    content,
+                    diagnostics: Some(ToolCallDiagnostics {
+                        tool_name: call.name.clone(),
+                        stdout: exec.stdout,
+                        stderr: exec.stderr,
+                        fuel_consumed: exec.fuel_consumed,
+                        trapped: exec.trapped,
+                    }),
+                }
+            }
+            Err(e) => ToolCallOutcome {
+                content: format!("Error: tool '{}' failed: {}", call.name, e),
+                diagnostics: None,
+            },
+        }
+    }
+}
+
+// What running a single tool call produced: the text to feed back to the model, and the
+// execution diagnostics when the call made it far enough to actually run.
+struct ToolCallOutcome {
+    content: String,
+    diagnostics: Option<ToolCallDiagnostics>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    // A scripted `LlmProvider`: hands back one `PlanOutput` per call, in order, and records
+    // the `history` it was given each time so tests can inspect how the loop fed tool
+    // results back. Cloning shares the same underlying state, so a test can keep a handle
+    // to inspect after handing a clone to `Brain`.
+    #[derive(Clone)]
+    struct FakeProvider {
+        responses: Arc<Mutex<VecDeque<PlanOutput>>>,
+        received_histories: Arc<Mutex<Vec<Vec<ChatMessage>>>>,
+    }
+
+    impl FakeProvider {
+        fn new(responses: Vec<PlanOutput>) -> Self {
+            Self {
+                responses: Arc::new(Mutex::new(responses.into())),
+                received_histories: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        fn histories(&self) -> Vec<Vec<ChatMessage>> {
+            self.received_histories.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for FakeProvider {
+        async fn plan(&self, _tools: &[ToolRecord], history: &[ChatMessage]) -> Result<PlanOutput> {
+            self.received_histories.lock().unwrap().push(history.to_vec());
+            let mut responses = self.responses.lock().unwrap();
+            Ok(responses.pop_front().expect("FakeProvider ran out of scripted responses"))
+        }
+    }
+
+    fn tool_call(id: &str, name: &str) -> ToolCall {
+        ToolCall { id: id.to_string(), name: name.to_string(), arguments: "{}".to_string() }
+    }
+
+    #[tokio::test]
+    async fn run_agent_gives_up_after_max_steps_instead_of_hanging() {
+        // Every step asks for a tool the (empty) registry doesn't have, so the model never
+        // reaches a final answer and the loop is forced all the way to its ceiling.
+        let responses: Vec<PlanOutput> = (0..MAX_AGENT_STEPS)
+            .map(|i| PlanOutput {
+                content: None,
+                tool_calls: vec![tool_call(&format!("call-{}", i), "missing-tool")],
+            })
+            .collect();
+        let brain = Brain { provider: Box::new(FakeProvider::new(responses)) };
+        let registry = Registry::for_tests(vec![]);
+        let runtime = Runtime::new().unwrap();
+
+        let err = brain.run_agent("do the thing", &registry, &runtime).await.unwrap_err();
+
+        match err {
+            AgentError::StepLimitExceeded { diagnostics, steps } => {
+                assert_eq!(steps, MAX_AGENT_STEPS);
+                // Nothing in the registry ever actually ran, so there's nothing to carry -
+                // the point is that this path returns rather than losing the run to a bail!.
+                assert!(diagnostics.is_empty());
+            }
+            other => panic!("expected AgentError::StepLimitExceeded, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_agent_dispatches_every_call_in_a_step_and_tags_results_by_id() {
+        let first_step = PlanOutput {
+            content: None,
+            tool_calls: vec![tool_call("call-a", "tool-a"), tool_call("call-b", "tool-b")],
+        };
+        let final_step = PlanOutput { content: Some("done".to_string()), tool_calls: vec![] };
+
+        let fake = FakeProvider::new(vec![first_step, final_step]);
+        let brain = Brain { provider: Box::new(fake.clone()) };
+        let registry = Registry::for_tests(vec![]);
+        let runtime = Runtime::new().unwrap();
+
+        let run = brain.run_agent("do two things", &registry, &runtime).await.unwrap();
+        assert_eq!(run.answer, "done");
+        assert_eq!(run.steps, 2);
+
+        // The second plan() call only happens once both tool results from the first batch
+        // have been fed back, and each one is tagged with its own call id rather than
+        // mixed up or dropped - exactly what the concurrent join_all dispatch has to get
+        // right.
+        let histories = fake.histories();
+        let second_call_history = &histories[1];
+        let tool_messages: Vec<(&str, &str)> = second_call_history
+            .iter()
+            .filter_map(|m| match m {
+                ChatMessage::Tool { tool_call_id, content } => Some((tool_call_id.as_str(), content.as_str())),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(tool_messages.len(), 2);
+        assert!(tool_messages.iter().any(|(id, content)| *id == "call-a" && content.contains("tool-a")));
+        assert!(tool_messages.iter().any(|(id, content)| *id == "call-b" && content.contains("tool-b")));
+    }
+}
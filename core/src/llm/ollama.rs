@@ -0,0 +1,215 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{ChatMessage, LlmProvider, PlanOutput, ToolCall};
+use crate::registry::ToolRecord;
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+// Talks to a local (or remote) Ollama server's `/api/chat` endpoint. Ollama's tool_calls
+// don't carry an id the way OpenAI's and Anthropic's do, so we mint one from the call's
+// position in the turn purely so the agent loop has something to tag the result with.
+pub struct OllamaProvider {
+    http: reqwest::Client,
+    base_url: String,
+    model: String,
+}
+
+impl OllamaProvider {
+    pub fn from_env() -> Result<Self> {
+        let base_url = std::env::var("OLLAMA_API_BASE")
+            .unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        let model = std::env::var("OLLAMA_MODEL")
+            .unwrap_or_else(|_| "llama3.1".to_string());
+
+        Ok(Self { http: reqwest::Client::new(), base_url, model })
+    }
+
+    fn build_tool_menu(tools: &[ToolRecord]) -> Vec<Value> {
+        tools.iter().map(|t| json!({
+            "type": "function",
+            "function": {
+                "name": t.name,
+                "description": t.description,
+                "parameters": t.parameters,
+            },
+        })).collect()
+    }
+
+    // Translates our provider-agnostic history into Ollama's chat message shape. Ollama
+    // matches tool results by order rather than an id, so the `tool_call_id` we carry
+    // internally is simply dropped here.
+    fn build_messages(history: &[ChatMessage]) -> Vec<Value> {
+        history.iter().map(|msg| match msg {
+            ChatMessage::User(content) => json!({ "role": "user", "content": content }),
+            ChatMessage::Assistant { content, tool_calls } => json!({
+                "role": "assistant",
+                "content": content.clone().unwrap_or_default(),
+                "tool_calls": tool_calls.iter().map(|c| json!({
+                    "function": {
+                        "name": c.name,
+                        "arguments": serde_json::from_str::<Value>(&c.arguments).unwrap_or_default(),
+                    },
+                })).collect::<Vec<_>>(),
+            }),
+            ChatMessage::Tool { content, .. } => json!({ "role": "tool", "content": content }),
+        }).collect()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    async fn plan(&self, tools: &[ToolRecord], history: &[ChatMessage]) -> Result<PlanOutput> {
+        let body = json!({
+            "model": self.model,
+            "messages": Self::build_messages(history),
+            "tools": Self::build_tool_menu(tools),
+            "stream": false,
+        });
+
+        let response = self.http
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&body)
+            .send()
+            .await
+            .context("Ollama request failed")?
+            .error_for_status()
+            .context("Ollama returned an error response")?
+            .json::<Value>()
+            .await
+            .context("Failed to parse Ollama response")?;
+
+        let message = &response["message"];
+        let content = message["content"].as_str().filter(|c| !c.is_empty()).map(str::to_string);
+
+        let tool_calls = message["tool_calls"].as_array().cloned().unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: format!("ollama-call-{}", i),
+                name: call["function"]["name"].as_str().unwrap_or_default().to_string(),
+                arguments: call["function"]["arguments"].to_string(),
+            })
+            .collect();
+
+        Ok(PlanOutput { content, tool_calls })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::llm::ToolCall;
+    use crate::registry::ToolLimits;
+
+    fn make_tool(name: &str) -> ToolRecord {
+        ToolRecord {
+            name: name.to_string(),
+            description: format!("does {}", name),
+            binary_path: "tools/whatever.wasm".to_string(),
+            handler: "run".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+            capabilities: Default::default(),
+            limits: ToolLimits::default(),
+            sha256: None,
+        }
+    }
+
+    // A one-shot HTTP server that ignores whatever it's sent and always replies with the
+    // given JSON body - same technique `core/src/registry.rs`'s tests use to exercise
+    // network-dependent code without a real network dependency.
+    fn spawn_static_json_server(body: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(body.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn make_provider(base_url: String) -> OllamaProvider {
+        OllamaProvider { http: reqwest::Client::new(), base_url, model: "llama3.1".to_string() }
+    }
+
+    #[test]
+    fn build_tool_menu_wraps_each_tool_in_a_function_envelope() {
+        let tools = vec![make_tool("search")];
+        let menu = OllamaProvider::build_tool_menu(&tools);
+
+        assert_eq!(menu.len(), 1);
+        assert_eq!(menu[0]["type"], "function");
+        assert_eq!(menu[0]["function"]["name"], "search");
+        assert_eq!(menu[0]["function"]["parameters"], tools[0].parameters);
+    }
+
+    #[test]
+    fn build_messages_drops_the_tool_call_id_ollama_cant_use() {
+        let history = vec![
+            ChatMessage::User("what's the weather?".to_string()),
+            ChatMessage::Assistant {
+                content: None,
+                tool_calls: vec![ToolCall {
+                    id: "call-1".to_string(),
+                    name: "weather".to_string(),
+                    arguments: r#"{"city":"nyc"}"#.to_string(),
+                }],
+            },
+            ChatMessage::Tool { tool_call_id: "call-1".to_string(), content: "72F".to_string() },
+        ];
+
+        let messages = OllamaProvider::build_messages(&history);
+
+        assert_eq!(messages[1]["tool_calls"][0]["function"]["name"], "weather");
+        assert_eq!(messages[1]["tool_calls"][0]["function"]["arguments"], json!({ "city": "nyc" }));
+        assert!(messages[1]["tool_calls"][0].get("id").is_none());
+        assert_eq!(messages[2], json!({ "role": "tool", "content": "72F" }));
+    }
+
+    #[tokio::test]
+    async fn plan_mints_synthetic_ids_for_each_tool_call_by_position() {
+        let body = r#"{
+            "message": {
+                "content": "",
+                "tool_calls": [
+                    { "function": { "name": "weather", "arguments": { "city": "nyc" } } },
+                    { "function": { "name": "clock", "arguments": {} } }
+                ]
+            }
+        }"#;
+        let base_url = spawn_static_json_server(body);
+        let provider = make_provider(base_url);
+
+        let output = provider.plan(&[], &[ChatMessage::User("what's the weather and time?".to_string())]).await.unwrap();
+
+        assert_eq!(output.content, None);
+        assert_eq!(output.tool_calls.len(), 2);
+        assert_eq!(output.tool_calls[0].id, "ollama-call-0");
+        assert_eq!(output.tool_calls[0].name, "weather");
+        assert_eq!(output.tool_calls[1].id, "ollama-call-1");
+        assert_eq!(output.tool_calls[1].name, "clock");
+    }
+
+    #[tokio::test]
+    async fn plan_treats_empty_content_as_no_content() {
+        let body = r#"{ "message": { "content": "", "tool_calls": [] } }"#;
+        let base_url = spawn_static_json_server(body);
+        let provider = make_provider(base_url);
+
+        let output = provider.plan(&[], &[ChatMessage::User("go".to_string())]).await.unwrap();
+
+        assert_eq!(output.content, None);
+        assert!(output.tool_calls.is_empty());
+    }
+}
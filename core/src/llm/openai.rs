@@ -0,0 +1,108 @@
+use async_openai::{
+    config::OpenAIConfig,
+    types::{
+        ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessageArgs,
+        ChatCompletionRequestMessage, ChatCompletionRequestToolMessageArgs,
+        ChatCompletionRequestUserMessageArgs, ChatCompletionTool, ChatCompletionToolArgs,
+        ChatCompletionToolType, CreateChatCompletionRequestArgs, FunctionCall, FunctionObjectArgs,
+    },
+    Client,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+use super::{ChatMessage, LlmProvider, PlanOutput, ToolCall};
+use crate::registry::ToolRecord;
+
+// The original backend: talks to the OpenAI (or any OpenAI-compatible) chat completions
+// API. `OPENAI_API_BASE` lets operators point this at a self-hosted proxy or gateway
+// without touching code.
+pub struct OpenAiProvider {
+    client: Client<OpenAIConfig>,
+    model: String,
+}
+
+impl OpenAiProvider {
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("OPENAI_API_KEY")
+            .context("OPENAI_API_KEY must be set in .env")?;
+        let model = std::env::var("OPENAI_MODEL")
+            .unwrap_or_else(|_| "gpt-4o".to_string());
+
+        let mut config = OpenAIConfig::new().with_api_key(api_key);
+        if let Ok(base) = std::env::var("OPENAI_API_BASE") {
+            config = config.with_api_base(base);
+        }
+
+        Ok(Self { client: Client::with_config(config), model })
+    }
+
+    fn build_tool_menu(tools: &[ToolRecord]) -> Vec<ChatCompletionTool> {
+        tools.iter().map(|t| {
+            ChatCompletionToolArgs::default()
+                .r#type(ChatCompletionToolType::Function)
+                .function(
+                    FunctionObjectArgs::default()
+                        .name(&t.name)
+                        .description(&t.description)
+                        .parameters(t.parameters.clone()) // Pass the JSON Schema directly
+                        .build()
+                        .unwrap()
+                )
+                .build()
+                .unwrap()
+        }).collect()
+    }
+
+    // Translates our provider-agnostic history into OpenAI's own message types.
+    fn build_messages(history: &[ChatMessage]) -> Result<Vec<ChatCompletionRequestMessage>> {
+        history.iter().map(|msg| match msg {
+            ChatMessage::User(content) => Ok(ChatCompletionRequestMessage::User(
+                ChatCompletionRequestUserMessageArgs::default()
+                    .content(content.clone())
+                    .build()?,
+            )),
+            ChatMessage::Assistant { content, tool_calls } => {
+                let mut builder = ChatCompletionRequestAssistantMessageArgs::default();
+                if let Some(content) = content {
+                    builder.content(content.clone());
+                }
+                if !tool_calls.is_empty() {
+                    builder.tool_calls(tool_calls.iter().map(|c| ChatCompletionMessageToolCall {
+                        id: c.id.clone(),
+                        r#type: ChatCompletionToolType::Function,
+                        function: FunctionCall { name: c.name.clone(), arguments: c.arguments.clone() },
+                    }).collect::<Vec<_>>());
+                }
+                Ok(ChatCompletionRequestMessage::Assistant(builder.build()?))
+            }
+            ChatMessage::Tool { tool_call_id, content } => Ok(ChatCompletionRequestMessage::Tool(
+                ChatCompletionRequestToolMessageArgs::default()
+                    .tool_call_id(tool_call_id.clone())
+                    .content(content.clone())
+                    .build()?,
+            )),
+        }).collect()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn plan(&self, tools: &[ToolRecord], history: &[ChatMessage]) -> Result<PlanOutput> {
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(&self.model)
+            .messages(Self::build_messages(history)?)
+            .tools(Self::build_tool_menu(tools))
+            .build()?;
+
+        let response = self.client.chat().create(request).await?;
+        let choice = &response.choices[0];
+
+        let tool_calls = choice.message.tool_calls.clone().unwrap_or_default()
+            .into_iter()
+            .map(|c| ToolCall { id: c.id, name: c.function.name, arguments: c.function.arguments })
+            .collect();
+
+        Ok(PlanOutput { content: choice.message.content.clone(), tool_calls })
+    }
+}
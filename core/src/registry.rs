@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use anyhow::Result;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
 use tokio::fs;
 use serde_json::Value; // <--- Import Value
 
@@ -9,35 +10,369 @@ use serde_json::Value; // <--- Import Value
 pub struct ToolRecord {
     pub name: String,
     pub description: String,
-    pub binary_path: String, // Relative path to .wasm
+    pub binary_path: String, // Relative path to .wasm, or a URL to fetch it from
     pub handler: String,     // Function name to call
-    
+
     // NEW: Capture the JSON Schema for parameters
     // This allows the Brain to know *how* to call the tool.
-    pub parameters: Value,   
+    pub parameters: Value,
+
+    // NEW: Least-privilege WASI grants for this tool. Tools that omit this field entirely
+    // get the fully sandboxed default: no preopened directories, no inherited env vars,
+    // no network.
+    #[serde(default)]
+    pub capabilities: ToolCapabilities,
+
+    // NEW: Resource budget enforced by the Runtime's StoreLimits + epoch deadline. Tools
+    // that omit this field get the crate-wide defaults below.
+    #[serde(default)]
+    pub limits: ToolLimits,
+
+    // NEW: Expected sha256 digest of the binary, lowercase hex. Required when `binary_path`
+    // is a URL (that's what pins the download and makes the cache content-addressed);
+    // optional but still checked when `binary_path` is a local file.
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+// Per-tool memory/table/instance ceilings and a wall-clock timeout, enforced via
+// `StoreLimits` and Wasmtime's epoch-based interruption respectively.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolLimits {
+    pub max_memory_bytes: usize,
+    pub max_table_elements: usize,
+    pub max_instances: usize,
+    pub timeout_ms: u64,
+}
+
+impl Default for ToolLimits {
+    fn default() -> Self {
+        Self {
+            max_memory_bytes: 256 * 1024 * 1024, // 256 MiB
+            max_table_elements: 10_000,
+            max_instances: 1,
+            timeout_ms: 5_000,
+        }
+    }
 }
 
-pub struct Registry;
+// What a tool's passport is allowed to touch on the host. Mirrors the shape of a
+// `WasiCtxBuilder`: preopened directories, an allowlist of env vars to forward, extra
+// args, and an opt-in network flag.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCapabilities {
+    #[serde(default)]
+    pub preopens: Vec<PreopenDir>,
+    #[serde(default)]
+    pub env: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub network: bool,
+}
+
+// A single host directory preopened into the guest's filesystem view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreopenDir {
+    pub host_path: String,
+    pub guest_path: String,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+// Where downloaded tool binaries are cached, keyed by their sha256 digest so the same
+// content never needs fetching twice and a stale/mismatched file is easy to spot.
+const CACHE_DIR: &str = "tools/.cache";
+
+pub struct Registry {
+    tools: Vec<ToolRecord>,
+    http: reqwest::Client,
+    cache_dir: PathBuf,
+}
 
 impl Registry {
-    // Reads tools.json and returns a list of available tools
-    pub async fn load() -> Result<Vec<ToolRecord>> {
+    // Reads tools.json and returns the registry of available tools, ready to resolve and
+    // run. Doesn't fetch anything remote up front — binaries are pulled lazily, on first use.
+    pub async fn load() -> Result<Self> {
         let path = "tools/tools.json";
-        
+
         // 1. Read the JSON file
         let content = fs::read_to_string(path).await
             .map_err(|e| anyhow::anyhow!("Failed to read registry at '{}': {}", path, e))?;
 
         // 2. Parse it
         let tools: Vec<ToolRecord> = serde_json::from_str(&content)?;
-        
-        // 3. Verify binaries exist (Sanity Check)
+
+        // 3. Sanity-check local binaries now; remote ones are verified when they're fetched.
         for tool in &tools {
-            if !Path::new(&tool.binary_path).exists() {
+            if !Self::is_remote(&tool.binary_path) && !Path::new(&tool.binary_path).exists() {
                 tracing::warn!("Tool '{}' registered but binary not found at: {}", tool.name, tool.binary_path);
             }
         }
 
-        Ok(tools)
+        let cache_dir = PathBuf::from(CACHE_DIR);
+        fs::create_dir_all(&cache_dir).await
+            .with_context(|| format!("Failed to create tool binary cache at '{}'", cache_dir.display()))?;
+
+        Ok(Self { tools, http: reqwest::Client::new(), cache_dir })
+    }
+
+    pub fn tools(&self) -> &[ToolRecord] {
+        &self.tools
+    }
+
+    fn is_remote(path: &str) -> bool {
+        path.starts_with("http://") || path.starts_with("https://")
+    }
+
+    // Lets other modules' tests build a `Registry` around a fixed tool list without reading
+    // tools.json off disk, e.g. the agent loop tests in `llm::tests`.
+    #[cfg(test)]
+    pub(crate) fn for_tests(tools: Vec<ToolRecord>) -> Self {
+        Self { tools, http: reqwest::Client::new(), cache_dir: std::env::temp_dir() }
+    }
+
+    // Resolves a tool's passport to a local, integrity-checked path to hand to
+    // `Runtime::run_tool`. Local binaries are verified against `sha256` in place when it's
+    // set; remote ones are downloaded into a content-addressed cache on first use (keyed by
+    // the digest the passport pins them to) and verified again on every cache hit, so a
+    // corrupted or tampered cache entry is never silently reused.
+    pub async fn resolve_binary(&self, tool: &ToolRecord) -> Result<PathBuf> {
+        if !Self::is_remote(&tool.binary_path) {
+            let path = PathBuf::from(&tool.binary_path);
+            if let Some(expected) = &tool.sha256 {
+                let bytes = fs::read(&path).await
+                    .with_context(|| format!("Failed to read '{}' for integrity check", path.display()))?;
+                Self::verify_digest(&tool.name, &bytes, expected)?;
+            }
+            return Ok(path);
+        }
+
+        let expected = tool.sha256.as_ref().with_context(|| {
+            format!(
+                "Tool '{}' has a remote binary_path but no sha256 pinned in its passport",
+                tool.name
+            )
+        })?;
+        Self::validate_digest_shape(&tool.name, expected)?;
+
+        let cached_path = self.cache_dir.join(format!("{}.wasm", expected));
+        if let Ok(bytes) = fs::read(&cached_path).await {
+            if Self::verify_digest(&tool.name, &bytes, expected).is_ok() {
+                return Ok(cached_path);
+            }
+            tracing::warn!(
+                "Cached binary for tool '{}' at '{}' failed its integrity check, refetching",
+                tool.name,
+                cached_path.display()
+            );
+        }
+
+        tracing::info!("Fetching tool '{}' binary from {}", tool.name, tool.binary_path);
+        let bytes = self
+            .http
+            .get(&tool.binary_path)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch tool '{}' from {}", tool.name, tool.binary_path))?
+            .error_for_status()
+            .with_context(|| format!("Tool '{}' registry returned an error status", tool.name))?
+            .bytes()
+            .await
+            .with_context(|| format!("Failed to download tool '{}' body", tool.name))?;
+
+        Self::verify_digest(&tool.name, &bytes, expected)?;
+
+        fs::write(&cached_path, &bytes).await.with_context(|| {
+            format!("Failed to write cached binary for tool '{}' to '{}'", tool.name, cached_path.display())
+        })?;
+
+        Ok(cached_path)
+    }
+
+    // Refuses to treat a passport's `sha256` as a cache filename unless it's exactly what a
+    // digest should look like (64 lowercase hex chars). Without this a crafted value like
+    // `../../etc/passwd` would let a tools.json entry write the download outside
+    // `tools/.cache` entirely.
+    fn validate_digest_shape(tool_name: &str, digest: &str) -> Result<()> {
+        let is_valid = digest.len() == 64 && digest.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+        if !is_valid {
+            anyhow::bail!(
+                "Tool '{}' has a malformed sha256 in its passport: expected 64 lowercase hex chars, got '{}'",
+                tool_name,
+                digest
+            );
+        }
+        Ok(())
+    }
+
+    // Refuses to hand back a binary whose sha256 doesn't match what the passport pinned.
+    fn verify_digest(tool_name: &str, bytes: &[u8], expected: &str) -> Result<()> {
+        let actual = format!("{:x}", Sha256::digest(bytes));
+        if !actual.eq_ignore_ascii_case(expected) {
+            anyhow::bail!(
+                "Integrity check failed for tool '{}': expected sha256 {} but got {}",
+                tool_name,
+                expected,
+                actual
+            );
+        }
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    fn make_tool(binary_path: &str, sha256: Option<String>) -> ToolRecord {
+        ToolRecord {
+            name: "test-tool".to_string(),
+            description: "a tool used only in tests".to_string(),
+            binary_path: binary_path.to_string(),
+            handler: "run".to_string(),
+            parameters: Value::Null,
+            capabilities: ToolCapabilities::default(),
+            limits: ToolLimits::default(),
+            sha256,
+        }
+    }
+
+    fn registry_with_cache_dir(cache_dir: PathBuf) -> Registry {
+        Registry { tools: Vec::new(), http: reqwest::Client::new(), cache_dir }
+    }
+
+    // Serves `body` once to whichever single connection shows up first, then stops.
+    fn spawn_static_http_server(body: &'static [u8]) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes());
+                let _ = stream.write_all(body);
+            }
+        });
+        format!("http://{}/tool.wasm", addr)
+    }
+
+    #[test]
+    fn verify_digest_accepts_matching_hash() {
+        let bytes = b"hello wasm";
+        let digest = format!("{:x}", Sha256::digest(bytes));
+        assert!(Registry::verify_digest("test-tool", bytes, &digest).is_ok());
+    }
+
+    #[test]
+    fn verify_digest_rejects_mismatched_hash() {
+        let bytes = b"hello wasm";
+        let wrong_digest = format!("{:x}", Sha256::digest(b"something else"));
+        assert!(Registry::verify_digest("test-tool", bytes, &wrong_digest).is_err());
+    }
+
+    #[test]
+    fn validate_digest_shape_accepts_64_lowercase_hex_chars() {
+        let digest = "a".repeat(64);
+        assert!(Registry::validate_digest_shape("test-tool", &digest).is_ok());
+    }
+
+    #[test]
+    fn validate_digest_shape_rejects_path_traversal() {
+        assert!(Registry::validate_digest_shape("test-tool", "../../../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn validate_digest_shape_rejects_uppercase_and_wrong_length() {
+        assert!(Registry::validate_digest_shape("test-tool", &"A".repeat(64)).is_err());
+        assert!(Registry::validate_digest_shape("test-tool", &"a".repeat(63)).is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_verifies_local_files_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("local.wasm");
+        let body = b"local wasm bytes";
+        tokio::fs::write(&path, body).await.unwrap();
+
+        let registry = registry_with_cache_dir(dir.path().join(".cache"));
+        let digest = format!("{:x}", Sha256::digest(body));
+        let tool = make_tool(path.to_str().unwrap(), Some(digest));
+
+        let resolved = registry.resolve_binary(&tool).await.unwrap();
+        assert_eq!(resolved, path);
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_rejects_local_file_with_wrong_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("local.wasm");
+        tokio::fs::write(&path, b"local wasm bytes").await.unwrap();
+
+        let registry = registry_with_cache_dir(dir.path().join(".cache"));
+        let tool = make_tool(path.to_str().unwrap(), Some("0".repeat(64)));
+
+        assert!(registry.resolve_binary(&tool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_requires_sha256_for_remote_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = registry_with_cache_dir(dir.path().to_path_buf());
+        let tool = make_tool("https://example.invalid/tool.wasm", None);
+
+        assert!(registry.resolve_binary(&tool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_uses_cache_without_refetching() {
+        let body = b"already cached wasm bytes";
+        let digest = format!("{:x}", Sha256::digest(body));
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::fs::write(cache_dir.path().join(format!("{}.wasm", digest)), body).unwrap();
+
+        let registry = registry_with_cache_dir(cache_dir.path().to_path_buf());
+        // Deliberately unroutable: if resolve_binary tried to refetch, this would fail fast
+        // instead of silently reusing the cache.
+        let tool = make_tool("http://127.0.0.1:1/tool.wasm", Some(digest.clone()));
+
+        let resolved = registry.resolve_binary(&tool).await.unwrap();
+        assert_eq!(resolved, cache_dir.path().join(format!("{}.wasm", digest)));
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_downloads_and_caches_on_first_use() {
+        let body: &'static [u8] = b"freshly downloaded wasm bytes";
+        let digest = format!("{:x}", Sha256::digest(body));
+        let url = spawn_static_http_server(body);
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        let registry = registry_with_cache_dir(cache_dir.path().to_path_buf());
+        let tool = make_tool(&url, Some(digest.clone()));
+
+        let resolved = registry.resolve_binary(&tool).await.unwrap();
+        assert_eq!(resolved, cache_dir.path().join(format!("{}.wasm", digest)));
+        assert_eq!(tokio::fs::read(&resolved).await.unwrap(), body);
+    }
+
+    #[tokio::test]
+    async fn resolve_binary_refetches_when_cached_copy_is_corrupt() {
+        let body: &'static [u8] = b"the real bytes after a refetch";
+        let digest = format!("{:x}", Sha256::digest(body));
+        let url = spawn_static_http_server(body);
+        let cache_dir = tempfile::tempdir().unwrap();
+        std::fs::write(cache_dir.path().join(format!("{}.wasm", digest)), b"corrupted cache entry").unwrap();
+
+        let registry = registry_with_cache_dir(cache_dir.path().to_path_buf());
+        let tool = make_tool(&url, Some(digest.clone()));
+
+        let resolved = registry.resolve_binary(&tool).await.unwrap();
+        assert_eq!(tokio::fs::read(&resolved).await.unwrap(), body);
+    }
+}
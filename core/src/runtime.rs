@@ -1,7 +1,44 @@
-use wasmtime::{Engine, Config, Store};
+use std::time::Duration;
+
+use wasmtime::{Engine, Config, Store, StoreLimits, StoreLimitsBuilder};
 use wasmtime::component::{Linker, Component};
-use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, ResourceTable, WasiView};
-use anyhow::Result;
+use wasmtime_wasi::pipe::MemoryOutputPipe;
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, ResourceTable, WasiView};
+use anyhow::{Context, Result};
+use thiserror::Error;
+
+use crate::registry::{ToolLimits, ToolRecord};
+
+// Capacity of each tool's in-memory stdout/stderr capture pipe. Guests that print past
+// this just have the overflow silently dropped by the pipe, same as any bounded buffer.
+const CAPTURE_BUFFER_BYTES: usize = 1 << 20; // 1 MiB
+
+// What actually happened inside the sandbox for one tool execution: the guest's return
+// value plus everything needed to tell a clean result apart from a crash.
+#[derive(Debug, Clone, Default)]
+pub struct ToolExecution {
+    pub result: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub fuel_consumed: u64,
+    pub trapped: bool,
+}
+
+// How often the background ticker bumps the engine's epoch. A tool's `timeout_ms` is
+// converted into a number of these ticks, so this is the timeout's effective granularity.
+const EPOCH_TICK: Duration = Duration::from_millis(50);
+
+// Clear, matchable reasons a tool execution can fail from resource exhaustion, as opposed
+// to a plain guest trap or host error (which stay in the catch-all `Other` variant).
+#[derive(Debug, Error)]
+pub enum RuntimeError {
+    #[error("tool exhausted its fuel budget")]
+    FuelExhausted,
+    #[error("tool exceeded its {0:?} wall-clock timeout")]
+    TimedOut(Duration),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
 
 // 1. Generate Host Traits from the WIT "Treaty"
 // This looks at the .wit file and creates Rust code to call the 'run' function.
@@ -16,6 +53,7 @@ wasmtime::component::bindgen!({
 pub struct IronClawCtx {
     wasi: WasiCtx,
     table: ResourceTable, // Required for Wasmtime resource management
+    limits: StoreLimits,  // Enforces a tool's memory/table/instance ceilings
 }
 
 // This trait tells Wasmtime how to get the WASI state from our struct
@@ -37,40 +75,274 @@ impl Runtime {
         config.wasm_component_model(true); // Enable the modern Component Model
         config.async_support(true);        // Allow async calls
         config.consume_fuel(true);         // Enable "Gas" metering (Security)
+        config.epoch_interruption(true);   // Enable wall-clock timeouts via epoch deadlines
 
         let engine = Engine::new(&config)?;
+
+        // Background ticker: the only thing that advances the engine's epoch. Every tool
+        // execution sets its own deadline some number of ticks out, so this single thread
+        // is what makes `ToolLimits::timeout_ms` actually fire.
+        let ticker_engine = engine.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(EPOCH_TICK);
+            ticker_engine.increment_epoch();
+        });
+
         Ok(Self { engine })
     }
 
-    // The Critical Function: Execute a Tool
-    pub async fn run_tool(&self, binary_path: &str, input_data: String) -> Result<String> {
+    // The Critical Function: Execute a Tool. `binary_path` is the already-resolved, already
+    // integrity-checked local path to load the component from — callers get it from
+    // `Registry::resolve_binary`, which is what actually deals with remote/cached passports.
+    pub async fn run_tool(
+        &self,
+        tool: &ToolRecord,
+        binary_path: &std::path::Path,
+        input_data: String,
+    ) -> Result<ToolExecution, RuntimeError> {
         // A. Prepare the Linker (Standard Lib)
         let mut linker = Linker::new(&self.engine);
         wasmtime_wasi::add_to_linker_async(&mut linker)?;
 
-        // B. Prepare the Context (Filesystem, Args)
-        // For now, we give it a basic context inheriting logs so we can see output.
-        let wasi = WasiCtxBuilder::new()
-            .inherit_stdio() 
-            .args(&["ironclaw-guest"]) 
-            .build();
+        // B. Prepare the Context (Filesystem, Args, Env, Network) from the tool's passport.
+        // Default is fully sandboxed: no inherited stdio, no filesystem, no network. A
+        // tool only gets more than that if its `capabilities` explicitly ask for it. Stdout
+        // and stderr are always captured into in-memory pipes rather than inherited, so
+        // guest output never leaks into the host's own logs.
+        let stdout_pipe = MemoryOutputPipe::new(CAPTURE_BUFFER_BYTES);
+        let stderr_pipe = MemoryOutputPipe::new(CAPTURE_BUFFER_BYTES);
+        let wasi = Self::build_wasi_ctx(&tool.capabilities, stdout_pipe.clone(), stderr_pipe.clone())?;
+        let limits = Self::build_store_limits(&tool.limits);
 
         let table = ResourceTable::new();
-        let ctx = IronClawCtx { wasi, table };
+        let ctx = IronClawCtx { wasi, table, limits };
 
-        // C. Initialize the Store (The Memory)
+        // C. Initialize the Store (The Memory) and wire up both budgets: fuel (work done)
+        // and the epoch deadline (wall-clock time), plus the StoreLimits memory ceiling.
         let mut store = Store::new(&self.engine, ctx);
         store.set_fuel(10_000_000)?; // Give it 10 million units of fuel
+        let fuel_before = store.get_fuel()?;
+        store.limiter(|ctx| &mut ctx.limits);
 
-        // D. Load the Binary from Disk
+        let deadline_ticks = (tool.limits.timeout_ms / EPOCH_TICK.as_millis() as u64).max(1);
+        store.set_epoch_deadline(deadline_ticks);
+
+        // D. Load the Binary from Disk (already resolved and integrity-checked by the caller)
         let component = Component::from_file(&self.engine, binary_path)?;
 
         // E. Instantiate (Boot the Guest)
         let tool_bindings = Tool::instantiate_async(&mut store, &component, &linker).await?;
-        
-        // F. Execute the 'run' function defined in the WIT
-        let result = tool_bindings.call_run(&mut store, &input_data).await?;
 
-        Ok(result)
+        // F. Execute the 'run' function defined in the WIT. A trap here (out of fuel, past
+        // its epoch deadline, or an ordinary guest panic) doesn't abort the whole call: we
+        // still want the captured stdout/stderr and fuel usage, so it's folded into the
+        // returned `ToolExecution` rather than propagated as an `Err`.
+        let call_result = tool_bindings.call_run(&mut store, &input_data).await;
+        let fuel_consumed = fuel_before.saturating_sub(store.get_fuel().unwrap_or(0));
+
+        let stdout = String::from_utf8_lossy(&stdout_pipe.contents()).into_owned();
+        let stderr = String::from_utf8_lossy(&stderr_pipe.contents()).into_owned();
+
+        let (result, trapped) = match call_result {
+            Ok(value) => (value, false),
+            Err(e) => (Self::classify_trap(e).to_string(), true),
+        };
+
+        Ok(ToolExecution { result, stdout, stderr, fuel_consumed, trapped })
+    }
+
+    // Turns a raw Wasmtime/guest error into one of our clear `RuntimeError` variants so
+    // callers can tell "ran out of fuel" and "took too long" apart from an ordinary trap.
+    fn classify_trap(err: anyhow::Error) -> RuntimeError {
+        if let Some(trap) = err.downcast_ref::<wasmtime::Trap>() {
+            if *trap == wasmtime::Trap::OutOfFuel {
+                return RuntimeError::FuelExhausted;
+            }
+            if *trap == wasmtime::Trap::Interrupt {
+                return RuntimeError::TimedOut(EPOCH_TICK);
+            }
+        }
+        RuntimeError::Other(err)
+    }
+
+    // Builds the `StoreLimits` (memory/table/instance ceilings) for a tool's budget.
+    fn build_store_limits(limits: &ToolLimits) -> StoreLimits {
+        StoreLimitsBuilder::new()
+            .memory_size(limits.max_memory_bytes)
+            .table_elements(limits.max_table_elements)
+            .instances(limits.max_instances)
+            .trap_on_grow_failure(true)
+            .build()
+    }
+
+    // Builds a least-privilege WASI context from a tool's capability grants. Nothing here
+    // is inherited from the host unless the passport asks for it by name; stdout/stderr
+    // always go to the supplied in-memory pipes instead of the host's own streams.
+    fn build_wasi_ctx(
+        capabilities: &crate::registry::ToolCapabilities,
+        stdout: MemoryOutputPipe,
+        stderr: MemoryOutputPipe,
+    ) -> Result<WasiCtx> {
+        let mut builder = WasiCtxBuilder::new();
+        builder.args(&["ironclaw-guest"]);
+        builder.stdout(stdout);
+        builder.stderr(stderr);
+
+        for extra_arg in &capabilities.args {
+            builder.arg(extra_arg);
+        }
+
+        for dir in &capabilities.preopens {
+            let host_dir = cap_std::fs::Dir::open_ambient_dir(&dir.host_path, cap_std::ambient_authority())
+                .with_context(|| format!("Failed to open preopen host path '{}'", dir.host_path))?;
+            let (dir_perms, file_perms) = Self::preopen_perms(dir.writable);
+            builder.preopened_dir(host_dir, dir_perms, file_perms, &dir.guest_path)?;
+        }
+
+        for (var, value) in Self::filtered_env(&capabilities.env) {
+            builder.env(&var, &value);
+        }
+
+        if capabilities.network {
+            builder.inherit_network();
+            builder.allow_ip_name_lookup(true);
+        }
+
+        Ok(builder.build())
+    }
+
+    // Grants full read/write on a preopen only when its passport marked it writable;
+    // everything else is strictly read-only.
+    fn preopen_perms(writable: bool) -> (DirPerms, FilePerms) {
+        if writable {
+            (DirPerms::all(), FilePerms::all())
+        } else {
+            (DirPerms::READ, FilePerms::READ)
+        }
+    }
+
+    // Resolves a tool's env allowlist against the host's actual environment: only
+    // variables named in `allowlist` are forwarded, and only if they're actually set.
+    fn filtered_env(allowlist: &[String]) -> Vec<(String, String)> {
+        allowlist
+            .iter()
+            .filter_map(|var| std::env::var(var).ok().map(|value| (var.clone(), value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::{PreopenDir, ToolCapabilities};
+    use wasmtime::ResourceLimiter;
+
+    #[test]
+    fn classify_trap_maps_out_of_fuel_to_fuel_exhausted() {
+        let err = anyhow::Error::new(wasmtime::Trap::OutOfFuel);
+        assert!(matches!(Runtime::classify_trap(err), RuntimeError::FuelExhausted));
+    }
+
+    #[test]
+    fn classify_trap_maps_interrupt_to_timed_out() {
+        let err = anyhow::Error::new(wasmtime::Trap::Interrupt);
+        match Runtime::classify_trap(err) {
+            RuntimeError::TimedOut(duration) => assert_eq!(duration, EPOCH_TICK),
+            other => panic!("expected TimedOut, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn classify_trap_falls_through_to_other_for_non_trap_errors() {
+        let err = anyhow::anyhow!("guest called an undefined import");
+        assert!(matches!(Runtime::classify_trap(err), RuntimeError::Other(_)));
+    }
+
+    #[test]
+    fn build_store_limits_enforces_the_configured_memory_ceiling() {
+        let limits = ToolLimits { max_memory_bytes: 64 * 1024, ..ToolLimits::default() };
+        let mut store_limits = Runtime::build_store_limits(&limits);
+
+        assert!(store_limits.memory_growing(0, 32 * 1024, None).unwrap());
+        assert!(!store_limits.memory_growing(0, 128 * 1024, None).unwrap());
+    }
+
+    #[test]
+    fn build_store_limits_default_allows_well_under_256_mib() {
+        let mut store_limits = Runtime::build_store_limits(&ToolLimits::default());
+        assert!(store_limits.memory_growing(0, 1024, None).unwrap());
+    }
+
+    #[test]
+    fn preopen_perms_grants_read_only_unless_writable() {
+        assert_eq!(Runtime::preopen_perms(false), (DirPerms::READ, FilePerms::READ));
+        assert_eq!(Runtime::preopen_perms(true), (DirPerms::all(), FilePerms::all()));
+    }
+
+    #[test]
+    fn filtered_env_only_forwards_allowlisted_vars_that_are_set() {
+        std::env::set_var("IRONCLAW_TEST_ALLOWED_VAR", "hello");
+        std::env::remove_var("IRONCLAW_TEST_UNSET_VAR");
+
+        let allowlist = vec![
+            "IRONCLAW_TEST_ALLOWED_VAR".to_string(),
+            "IRONCLAW_TEST_UNSET_VAR".to_string(),
+        ];
+        let forwarded = Runtime::filtered_env(&allowlist);
+
+        assert_eq!(forwarded, vec![("IRONCLAW_TEST_ALLOWED_VAR".to_string(), "hello".to_string())]);
+    }
+
+    #[test]
+    fn filtered_env_forwards_nothing_for_an_empty_allowlist() {
+        assert!(Runtime::filtered_env(&[]).is_empty());
+    }
+
+    #[test]
+    fn build_wasi_ctx_succeeds_with_default_capabilities() {
+        let stdout = MemoryOutputPipe::new(1024);
+        let stderr = MemoryOutputPipe::new(1024);
+        assert!(Runtime::build_wasi_ctx(&ToolCapabilities::default(), stdout, stderr).is_ok());
+    }
+
+    #[test]
+    fn build_wasi_ctx_preopens_both_read_only_and_writable_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let capabilities = ToolCapabilities {
+            preopens: vec![
+                PreopenDir {
+                    host_path: dir.path().to_string_lossy().into_owned(),
+                    guest_path: "/ro".to_string(),
+                    writable: false,
+                },
+                PreopenDir {
+                    host_path: dir.path().to_string_lossy().into_owned(),
+                    guest_path: "/rw".to_string(),
+                    writable: true,
+                },
+            ],
+            ..ToolCapabilities::default()
+        };
+
+        let stdout = MemoryOutputPipe::new(1024);
+        let stderr = MemoryOutputPipe::new(1024);
+        assert!(Runtime::build_wasi_ctx(&capabilities, stdout, stderr).is_ok());
+    }
+
+    #[test]
+    fn build_wasi_ctx_reports_a_missing_preopen_host_path() {
+        let capabilities = ToolCapabilities {
+            preopens: vec![PreopenDir {
+                host_path: "/does/not/exist/ironclaw-test".to_string(),
+                guest_path: "/missing".to_string(),
+                writable: false,
+            }],
+            ..ToolCapabilities::default()
+        };
+
+        let stdout = MemoryOutputPipe::new(1024);
+        let stderr = MemoryOutputPipe::new(1024);
+        let err = Runtime::build_wasi_ctx(&capabilities, stdout, stderr).unwrap_err();
+        assert!(err.to_string().contains("/does/not/exist/ironclaw-test"));
     }
 }
\ No newline at end of file
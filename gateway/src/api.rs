@@ -17,9 +17,66 @@ pub struct ToolDef {
     pub parameters: Value, // Flexible JSON schema
 }
 
-// Output: What we send back
+// Output: the immediate acknowledgement returned by POST /run. The agent run itself
+// happens asynchronously on a worker; poll GET /jobs/{id} for its outcome.
 #[derive(Debug, Serialize)]
 pub struct RunResponse {
     pub job_id: String,
     pub status: String,
+}
+
+// Output: the current state of a submitted job, returned by GET /jobs/{id} and GET /jobs.
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    pub job_id: String,
+    pub tenant_id: String,
+    pub task: String,
+    pub status: String,
+    pub submitted_at: String,
+    pub finished_at: Option<String>,
+    pub result: Option<String>,
+    // How many plan/execute turns the agent loop took. 0 until the job finishes.
+    pub steps: i64,
+    // Per-tool-call execution diagnostics gathered across the run, so callers can see what
+    // actually happened inside the sandbox rather than just the final answer.
+    pub tool_diagnostics: Vec<ToolDiagnostics>,
+}
+
+impl From<crate::jobs::Job> for JobResponse {
+    fn from(job: crate::jobs::Job) -> Self {
+        Self {
+            job_id: job.id,
+            tenant_id: job.tenant_id,
+            task: job.task,
+            status: job.status,
+            submitted_at: job.submitted_at,
+            finished_at: job.finished_at,
+            result: job.result,
+            steps: job.steps,
+            tool_diagnostics: job.diagnostics.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+// Structured output captured from a single sandboxed tool execution: what it printed,
+// whether it trapped, and how much fuel it burned.
+#[derive(Debug, Serialize)]
+pub struct ToolDiagnostics {
+    pub tool_name: String,
+    pub stdout: String,
+    pub stderr: String,
+    pub fuel_consumed: u64,
+    pub trapped: bool,
+}
+
+impl From<core::llm::ToolCallDiagnostics> for ToolDiagnostics {
+    fn from(d: core::llm::ToolCallDiagnostics) -> Self {
+        Self {
+            tool_name: d.tool_name,
+            stdout: d.stdout,
+            stderr: d.stderr,
+            fuel_consumed: d.fuel_consumed,
+            trapped: d.trapped,
+        }
+    }
 }
\ No newline at end of file
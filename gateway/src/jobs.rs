@@ -0,0 +1,342 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
+use sqlx::{FromRow, SqlitePool};
+use std::str::FromStr;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use core::llm::{AgentError, Brain, ToolCallDiagnostics};
+use core::registry::Registry;
+use core::runtime::Runtime;
+
+// How long an idle worker waits before polling the queue again.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// How long a connection waits on a lock held by another writer before giving up with
+// SQLITE_BUSY. Needed because several workers share one database file.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, FromRow)]
+struct JobRow {
+    id: String,
+    tenant_id: String,
+    task: String,
+    status: String,
+    submitted_at: String,
+    finished_at: Option<String>,
+    result: Option<String>,
+    diagnostics_json: Option<String>,
+    steps: i64,
+}
+
+// A submitted agent run, persisted in SQLite so it survives gateway restarts and so
+// `/jobs` and `/jobs/{id}` have somewhere durable to read from.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    pub tenant_id: String,
+    pub task: String,
+    pub status: String,
+    pub submitted_at: String,
+    pub finished_at: Option<String>,
+    pub result: Option<String>,
+    pub diagnostics: Vec<ToolCallDiagnostics>,
+    pub steps: i64,
+}
+
+impl From<JobRow> for Job {
+    fn from(row: JobRow) -> Self {
+        let diagnostics = row.diagnostics_json
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default();
+
+        Self {
+            id: row.id,
+            tenant_id: row.tenant_id,
+            task: row.task,
+            status: row.status,
+            submitted_at: row.submitted_at,
+            finished_at: row.finished_at,
+            result: row.result,
+            diagnostics,
+            steps: row.steps,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct JobStore {
+    pool: SqlitePool,
+}
+
+impl JobStore {
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        // WAL lets readers and the one active writer proceed concurrently instead of
+        // serializing on the default rollback journal, and the busy timeout makes a worker
+        // wait out a momentary lock instead of failing its transaction with SQLITE_BUSY -
+        // both needed since every worker in the pool shares this one database file.
+        let connect_options = SqliteConnectOptions::from_str(database_url)
+            .with_context(|| format!("Failed to parse job store URL '{}'", database_url))?
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(BUSY_TIMEOUT);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(8)
+            .connect_with(connect_options)
+            .await
+            .with_context(|| format!("Failed to open job store at '{}'", database_url))?;
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS jobs (
+                id TEXT PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                task TEXT NOT NULL,
+                status TEXT NOT NULL,
+                submitted_at TEXT NOT NULL,
+                finished_at TEXT,
+                result TEXT,
+                diagnostics_json TEXT,
+                steps INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+
+    // Persists a brand new job in the `queued` state and hands back its generated id.
+    pub async fn enqueue(&self, tenant_id: &str, task: &str) -> Result<Job> {
+        let id = Uuid::new_v4().to_string();
+        let submitted_at = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO jobs (id, tenant_id, task, status, submitted_at) VALUES (?, ?, ?, 'queued', ?)",
+        )
+        .bind(&id)
+        .bind(tenant_id)
+        .bind(task)
+        .bind(&submitted_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Job {
+            id,
+            tenant_id: tenant_id.to_string(),
+            task: task.to_string(),
+            status: "queued".to_string(),
+            submitted_at,
+            finished_at: None,
+            result: None,
+            diagnostics: Vec::new(),
+            steps: 0,
+        })
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<Job>> {
+        let row = sqlx::query_as::<_, JobRow>("SELECT * FROM jobs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(Job::from))
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: &str, limit: i64) -> Result<Vec<Job>> {
+        let rows = sqlx::query_as::<_, JobRow>(
+            "SELECT * FROM jobs WHERE tenant_id = ? ORDER BY submitted_at DESC LIMIT ?",
+        )
+        .bind(tenant_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Job::from).collect())
+    }
+
+    // Atomically claims the oldest still-queued job so two workers never pick up the same
+    // one: the SELECT and the queued->running UPDATE happen inside one transaction.
+    async fn claim_next(&self) -> Result<Option<Job>> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query_as::<_, JobRow>(
+            "SELECT * FROM jobs WHERE status = 'queued' ORDER BY submitted_at LIMIT 1",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        sqlx::query("UPDATE jobs SET status = 'running' WHERE id = ? AND status = 'queued'")
+            .bind(&row.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(Some(Job { status: "running".to_string(), ..Job::from(row) }))
+    }
+
+    async fn mark_finished(&self, id: &str, result: &str, diagnostics: &[ToolCallDiagnostics], steps: usize) -> Result<()> {
+        let diagnostics_json = serde_json::to_string(diagnostics)?;
+        sqlx::query(
+            "UPDATE jobs SET status = 'finished', finished_at = ?, result = ?, diagnostics_json = ?, steps = ? WHERE id = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(result)
+        .bind(diagnostics_json)
+        .bind(steps as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // `diagnostics` carries whatever tool calls already ran before the failure, e.g. the
+    // steps an agent run took on its way to hitting the step limit - empty for failures
+    // that never got that far. `steps` mirrors `mark_finished`'s so a job that fails at
+    // the step limit still reports an accurate count instead of the `enqueue`-time default.
+    async fn mark_failed(&self, id: &str, error: &str, diagnostics: &[ToolCallDiagnostics], steps: usize) -> Result<()> {
+        let diagnostics_json = serde_json::to_string(diagnostics)?;
+        sqlx::query(
+            "UPDATE jobs SET status = 'failed', finished_at = ?, result = ?, diagnostics_json = ?, steps = ? WHERE id = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(error)
+        .bind(diagnostics_json)
+        .bind(steps as i64)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+// One agent worker: polls the queue for the oldest job, runs it end to end against the
+// shared Brain/Registry/Runtime, and persists the outcome. A pool of these (sized to CPU
+// count) is spawned once at gateway startup so request latency is decoupled from how long
+// the agent loop actually takes.
+pub async fn run_worker(
+    worker_id: usize,
+    jobs: JobStore,
+    brain: Arc<Brain>,
+    registry: Arc<Registry>,
+    runtime: Arc<Runtime>,
+) {
+    info!("Agent worker {} starting", worker_id);
+
+    loop {
+        match jobs.claim_next().await {
+            Ok(Some(job)) => {
+                info!("Worker {} picked up job {} ('{}')", worker_id, job.id, job.task);
+
+                match brain.run_agent(&job.task, &registry, &runtime).await {
+                    Ok(run) => {
+                        if let Err(e) = jobs.mark_finished(&job.id, &run.answer, &run.diagnostics, run.steps).await {
+                            error!("Worker {} failed to persist result for job {}: {}", worker_id, job.id, e);
+                        }
+                    }
+                    Err(AgentError::StepLimitExceeded { diagnostics, steps }) => {
+                        let message = format!(
+                            "agent exceeded max step limit ({}) without reaching a final answer",
+                            steps
+                        );
+                        error!("Worker {} job {} failed: {}", worker_id, job.id, message);
+                        if let Err(persist_err) = jobs.mark_failed(&job.id, &message, &diagnostics, steps).await {
+                            error!("Worker {} failed to persist failure for job {}: {}", worker_id, job.id, persist_err);
+                        }
+                    }
+                    Err(AgentError::Other(e)) => {
+                        error!("Worker {} job {} failed: {}", worker_id, job.id, e);
+                        if let Err(persist_err) = jobs.mark_failed(&job.id, &e.to_string(), &[], 0).await {
+                            error!("Worker {} failed to persist failure for job {}: {}", worker_id, job.id, persist_err);
+                        }
+                    }
+                }
+            }
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                error!("Worker {} failed to poll job queue: {}", worker_id, e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh, file-backed job store. Has to be file-backed rather than `sqlite::memory:`
+    // since every pooled connection to an in-memory database would otherwise see its own
+    // empty database instead of sharing one.
+    async fn temp_store() -> JobStore {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("jobs.db");
+        let store = JobStore::connect(&format!("sqlite://{}?mode=rwc", db_path.display()))
+            .await
+            .unwrap();
+        // Leaked so the backing directory outlives the pool for the rest of the test; these
+        // are short-lived test processes so this doesn't accumulate.
+        std::mem::forget(dir);
+        store
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn claim_next_is_exclusive_across_concurrent_callers() {
+        let store = temp_store().await;
+        for i in 0..20 {
+            store.enqueue("tenant-a", &format!("task-{}", i)).await.unwrap();
+        }
+
+        let mut handles = Vec::new();
+        for _ in 0..20 {
+            let store = store.clone();
+            handles.push(tokio::spawn(async move { store.claim_next().await.unwrap() }));
+        }
+
+        let mut claimed_ids = Vec::new();
+        for handle in handles {
+            if let Some(job) = handle.await.unwrap() {
+                claimed_ids.push(job.id);
+            }
+        }
+
+        let total_claimed = claimed_ids.len();
+        claimed_ids.sort();
+        claimed_ids.dedup();
+
+        assert_eq!(total_claimed, 20, "every queued job should be claimed exactly once");
+        assert_eq!(claimed_ids.len(), total_claimed, "no job should be claimed by more than one caller");
+    }
+
+    #[tokio::test]
+    async fn claim_next_moves_a_job_from_queued_to_running() {
+        let store = temp_store().await;
+        let enqueued = store.enqueue("tenant-a", "do something").await.unwrap();
+        assert_eq!(enqueued.status, "queued");
+
+        let claimed = store.claim_next().await.unwrap().unwrap();
+        assert_eq!(claimed.id, enqueued.id);
+        assert_eq!(claimed.status, "running");
+
+        assert!(store.claim_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn mark_failed_persists_the_step_count() {
+        let store = temp_store().await;
+        let job = store.enqueue("tenant-a", "do something").await.unwrap();
+
+        store.mark_failed(&job.id, "agent exceeded max step limit (8)", &[], 8).await.unwrap();
+
+        let reloaded = store.get(&job.id).await.unwrap().unwrap();
+        assert_eq!(reloaded.status, "failed");
+        assert_eq!(reloaded.steps, 8);
+    }
+}
@@ -1,28 +1,37 @@
 mod api;
+mod jobs;
 
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
+    http::StatusCode,
     routing::{get, post},
     Json, Router,
 };
+use serde::Deserialize;
 use tokio::net::TcpListener;
 use tracing::{info, error};
 use tower_http::trace::TraceLayer;
 use std::sync::Arc;
 
 // Internal imports
-use crate::api::{RunRequest, RunResponse};
-use core::registry::{Registry, ToolRecord};
+use crate::api::{JobResponse, RunRequest, RunResponse};
+use crate::jobs::JobStore;
+use core::registry::Registry;
 use core::runtime::Runtime;
 use core::llm::Brain;
 
+// How many jobs `GET /jobs` returns per tenant when the caller doesn't ask for more.
+const DEFAULT_JOB_LIST_LIMIT: i64 = 50;
+
 // 1. Define Application State
-// Now holds all three critical components: Brain (Logic), Registry (Memory), Runtime (Body)
+// Now holds all four critical components: Brain (Logic), Registry (Memory), Runtime (Body),
+// and the JobStore (durable history of every run submitted).
 #[derive(Clone)]
 struct AppState {
     runtime: Arc<Runtime>,
     brain: Arc<Brain>,
-    registry: Arc<Vec<ToolRecord>>,
+    registry: Arc<Registry>,
+    jobs: JobStore,
 }
 
 #[tokio::main]
@@ -53,32 +62,57 @@ async fn main() {
     // 4. Load The Registry (The Menu)
     // We load this once into memory so we can pass it to the Brain on every request.
     info!("Loading Tool Registry...");
-    let tools = Registry::load().await.expect("Failed to load tool registry");
-    let registry = Arc::new(tools);
-    info!("Loaded {} tools available for the Brain.", registry.len());
+    let registry = Arc::new(Registry::load().await.expect("Failed to load tool registry"));
+    info!("Loaded {} tools available for the Brain.", registry.tools().len());
 
     // 5. Initialize The Runtime (The Body)
     info!("Initializing Wasmtime Runtime...");
     let runtime = Arc::new(Runtime::new().expect("Failed to initialize Wasmtime Runtime"));
 
+    // 5.5 Open the Job Store (Durable History)
+    // Every submitted run lives here so request latency no longer depends on how long the
+    // agent loop takes, and history survives a gateway restart.
+    info!("Opening job store...");
+    let database_url = std::env::var("IRONCLAW_DB_URL")
+        .unwrap_or_else(|_| "sqlite://ironclaw.db?mode=rwc".to_string());
+    let jobs = JobStore::connect(&database_url).await.expect("Failed to open job store");
+
     // 6. Bundle State
     let state = AppState {
         runtime,
         brain,
         registry,
+        jobs,
     };
 
+    // 6.5 Spawn the Worker Pool
+    // Sized to the host's CPU count, each worker polls the queue for the oldest job and
+    // runs it end to end against the shared Brain/Registry/Runtime.
+    let worker_count = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    info!("Spawning {} agent worker(s)...", worker_count);
+    for worker_id in 0..worker_count {
+        tokio::spawn(jobs::run_worker(
+            worker_id,
+            state.jobs.clone(),
+            state.brain.clone(),
+            state.registry.clone(),
+            state.runtime.clone(),
+        ));
+    }
+
     // 7. Define Routes
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/run", post(submit_run))
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/:id", get(get_job))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
     // 8. Start Server
     let listener = TcpListener::bind("0.0.0.0:3000").await.unwrap();
     info!("Gateway listening on port 3000...");
-    
+
     axum::serve(listener, app).await.unwrap();
 }
 
@@ -88,82 +122,64 @@ async fn health_check() -> &'static str {
     "IronClaw Gateway: Operational"
 }
 
-// The Orchestrator Handler
+// The Intake Handler: enqueues the run and returns immediately. The agent loop itself
+// happens on a background worker; callers poll `GET /jobs/{id}` for the outcome.
 async fn submit_run(
     State(state): State<AppState>,
     Json(payload): Json<RunRequest>
 ) -> Json<RunResponse> {
-    info!("Tenant '{}' requested: {}", payload.tenant_id, payload.task);
-
-    // STEP 1: THINK (The Brain)
-    info!("Brain is planning execution...");
-    
-    // We ask the Brain: "Here is the user's task, and here is the list of tools. Should we run one?"
-    let plan_result = state.brain.plan(&payload.task, &state.registry).await;
-
-    match plan_result {
-        Ok(Some(tool_call_json)) => {
-            // STEP 2: DECIDE (The Plan)
-            // The LLM has returned a JSON object describing the tool call.
-            // Example: { "function": { "name": "ironclaw_echo", "arguments": "{\"input\":\"Hello\"}" } }
-            
-            let function_name = tool_call_json["function"]["name"].as_str().unwrap_or("unknown");
-            let arguments_str = tool_call_json["function"]["arguments"].as_str().unwrap_or("{}");
-
-            info!("Brain decided to call tool: '{}'", function_name);
-            info!("Arguments: {}", arguments_str);
-
-            // STEP 3: LOCATE (The Registry Lookup)
-            if let Some(tool_record) = state.registry.iter().find(|t| t.name == function_name) {
-                
-                // Parse arguments to find 'input' (since our current interface takes a single string)
-                let args_obj: serde_json::Value = serde_json::from_str(arguments_str).unwrap_or_default();
-                let input_val = args_obj["input"].as_str().unwrap_or("").to_string();
-
-                info!("Locating binary at: {}", tool_record.binary_path);
-                info!("Executing WASM Sandbox...");
-
-                // STEP 4: ACT (The Execution)
-                match state.runtime.run_tool(&tool_record.binary_path, input_val).await {
-                    Ok(output) => {
-                        info!("Tool Execution Success. Output size: {} bytes", output.len());
-                        info!("Result: {}", output);
-                        
-                        Json(RunResponse { 
-                            job_id: "ai-exec-success".to_string(), 
-                            status: output 
-                        })
-                    }
-                    Err(e) => {
-                        error!("Tool Execution Failed: {}", e);
-                        Json(RunResponse { 
-                            job_id: "ai-exec-failed".to_string(), 
-                            status: format!("Runtime Error: {}", e) 
-                        })
-                    }
-                }
-            } else {
-                error!("Brain hallucinated a tool that does not exist in registry: {}", function_name);
-                Json(RunResponse { 
-                    job_id: "err-hallucination".to_string(), 
-                    status: format!("Error: Tool '{}' not found", function_name) 
-                })
-            }
-        }
-        Ok(None) => {
-            info!("Brain decided NO tool was needed. Returning standard chat response.");
-            // In a full implementation, we would return the LLM's chat text here.
-            Json(RunResponse { 
-                job_id: "chat-only".to_string(), 
-                status: "I understood your request, but I don't need to run any tools to answer it.".to_string() 
+    info!("Tenant '{}' submitted: {}", payload.tenant_id, payload.task);
+
+    match state.jobs.enqueue(&payload.tenant_id, &payload.task).await {
+        Ok(job) => Json(RunResponse { job_id: job.id, status: job.status }),
+        Err(e) => {
+            error!("Failed to enqueue job for tenant '{}': {}", payload.tenant_id, e);
+            Json(RunResponse {
+                job_id: String::new(),
+                status: format!("Error: failed to enqueue job: {}", e),
             })
         }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GetJobQuery {
+    tenant_id: String,
+}
+
+// Scoped the same way `list_jobs` is: a job belonging to another tenant is reported as
+// NOT_FOUND rather than leaking its task/answer/diagnostics to whoever guesses its id.
+async fn get_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<GetJobQuery>,
+) -> Result<Json<JobResponse>, StatusCode> {
+    match state.jobs.get(&id).await {
+        Ok(Some(job)) if job.tenant_id == query.tenant_id => Ok(Json(job.into())),
+        Ok(_) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            error!("Brain Failure: {}", e);
-            Json(RunResponse { 
-                job_id: "err-brain".to_string(), 
-                status: "Internal AI Error".to_string() 
-            })
+            error!("Failed to look up job '{}': {}", id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListJobsQuery {
+    tenant_id: String,
+    limit: Option<i64>,
+}
+
+async fn list_jobs(
+    State(state): State<AppState>,
+    Query(query): Query<ListJobsQuery>,
+) -> Result<Json<Vec<JobResponse>>, StatusCode> {
+    let limit = query.limit.unwrap_or(DEFAULT_JOB_LIST_LIMIT);
+    match state.jobs.list_for_tenant(&query.tenant_id, limit).await {
+        Ok(jobs) => Ok(Json(jobs.into_iter().map(Into::into).collect())),
+        Err(e) => {
+            error!("Failed to list jobs for tenant '{}': {}", query.tenant_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,248 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+// How long to wait between polls of a submitted job, and how long to wait in total before
+// giving up on a single task run.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const POLL_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Tenant id every bench run submits under, so polled jobs can be scoped the same way
+// `GET /jobs/{id}` now requires of every other caller.
+const BENCH_TENANT_ID: &str = "xtask-bench";
+
+// --- Workload file schema ---
+
+#[derive(Debug, Deserialize)]
+pub struct Workload {
+    pub name: String,
+    #[serde(default = "default_repetitions")]
+    pub repetitions: u32,
+    pub tasks: Vec<TaskSpec>,
+}
+
+fn default_repetitions() -> u32 {
+    1
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TaskSpec {
+    pub task: String,
+    pub expected_tool: Option<String>,
+}
+
+// --- CLI args for `cargo xtask bench` ---
+
+pub struct BenchArgs {
+    workload_path: String,
+    gateway_url: String,
+    results_url: Option<String>,
+}
+
+impl BenchArgs {
+    // Parses `--workload <path>` (required), `--gateway-url <url>` (default
+    // http://localhost:3000) and `--results-url <url>` (optional) from the subcommand's args.
+    pub fn parse(args: Vec<String>) -> Result<Self> {
+        let mut workload_path = None;
+        let mut gateway_url = "http://localhost:3000".to_string();
+        let mut results_url = None;
+
+        let mut iter = args.into_iter();
+        while let Some(flag) = iter.next() {
+            match flag.as_str() {
+                "--workload" => workload_path = Some(iter.next().context("--workload needs a value")?),
+                "--gateway-url" => gateway_url = iter.next().context("--gateway-url needs a value")?,
+                "--results-url" => results_url = Some(iter.next().context("--results-url needs a value")?),
+                other => bail!("unknown flag '{}'", other),
+            }
+        }
+
+        Ok(Self {
+            workload_path: workload_path.context("--workload <path> is required")?,
+            gateway_url,
+            results_url,
+        })
+    }
+}
+
+// --- Gateway wire types (mirrors gateway::api) ---
+
+#[derive(Debug, Deserialize)]
+struct RunResponse {
+    job_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolDiagnostics {
+    tool_name: String,
+    fuel_consumed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct JobResponse {
+    status: String,
+    steps: i64,
+    tool_diagnostics: Vec<ToolDiagnostics>,
+}
+
+// --- Report ---
+
+#[derive(Debug, Serialize)]
+struct TaskRun {
+    task: String,
+    latency_ms: f64,
+    fuel_consumed: u64,
+    agent_steps: i64,
+    tool_hit: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    workload: String,
+    gateway_url: String,
+    total_runs: usize,
+    min_latency_ms: f64,
+    median_latency_ms: f64,
+    p95_latency_ms: f64,
+    mean_fuel_consumed: f64,
+    tool_hit_rate: f64,
+    hallucination_rate: f64,
+    runs: Vec<TaskRun>,
+}
+
+pub async fn run(args: BenchArgs) -> Result<()> {
+    let content = std::fs::read_to_string(&args.workload_path)
+        .with_context(|| format!("Failed to read workload file '{}'", args.workload_path))?;
+    let workload: Workload = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse workload file '{}'", args.workload_path))?;
+
+    let client = reqwest::Client::new();
+    let mut runs = Vec::new();
+
+    for task in &workload.tasks {
+        for rep in 1..=workload.repetitions {
+            info!("[{}] {}/{}: {}", workload.name, rep, workload.repetitions, task.task);
+            runs.push(drive_task(&client, &args.gateway_url, task).await?);
+        }
+    }
+
+    let report = summarize(&workload.name, &args.gateway_url, runs);
+
+    let report_json = serde_json::to_string_pretty(&report)?;
+    println!("{}", report_json);
+
+    if let Some(results_url) = &args.results_url {
+        client.post(results_url)
+            .json(&report)
+            .send()
+            .await
+            .context("Failed to POST bench report")?
+            .error_for_status()
+            .context("Results collector returned an error")?;
+        info!("Posted bench report to {}", results_url);
+    }
+
+    Ok(())
+}
+
+// Submits one task run against the gateway, polls `/jobs/{id}` until it leaves the
+// queued/running states, and turns the result into a `TaskRun` sample.
+async fn drive_task(client: &reqwest::Client, gateway_url: &str, task: &TaskSpec) -> Result<TaskRun> {
+    let started = Instant::now();
+
+    let submitted: RunResponse = client
+        .post(format!("{}/run", gateway_url))
+        .json(&serde_json::json!({ "tenant_id": BENCH_TENANT_ID, "task": task.task, "tools": [] }))
+        .send()
+        .await
+        .context("Failed to submit task to gateway")?
+        .json()
+        .await
+        .context("Failed to parse /run response")?;
+
+    let job = poll_until_done(client, gateway_url, &submitted.job_id, BENCH_TENANT_ID).await?;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let fuel_consumed: u64 = job.tool_diagnostics.iter().map(|d| d.fuel_consumed).sum();
+    // With no expected tool we can't call it a hallucination either way; count it a hit.
+    let tool_hit = match &task.expected_tool {
+        Some(expected) => job.tool_diagnostics.iter().any(|d| &d.tool_name == expected),
+        None => true,
+    };
+
+    Ok(TaskRun {
+        task: task.task.clone(),
+        latency_ms,
+        fuel_consumed,
+        agent_steps: job.steps,
+        tool_hit,
+    })
+}
+
+async fn poll_until_done(
+    client: &reqwest::Client,
+    gateway_url: &str,
+    job_id: &str,
+    tenant_id: &str,
+) -> Result<JobResponse> {
+    let deadline = Instant::now() + POLL_TIMEOUT;
+
+    loop {
+        let job: JobResponse = client
+            .get(format!("{}/jobs/{}", gateway_url, job_id))
+            .query(&[("tenant_id", tenant_id)])
+            .send()
+            .await
+            .context("Failed to poll job status")?
+            .json()
+            .await
+            .context("Failed to parse /jobs/{id} response")?;
+
+        if job.status == "finished" || job.status == "failed" {
+            return Ok(job);
+        }
+
+        if Instant::now() >= deadline {
+            bail!("Job '{}' did not finish within {:?}", job_id, POLL_TIMEOUT);
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn summarize(workload_name: &str, gateway_url: &str, runs: Vec<TaskRun>) -> BenchReport {
+    let mut latencies: Vec<f64> = runs.iter().map(|r| r.latency_ms).collect();
+    latencies.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let total_runs = runs.len();
+    let hits = runs.iter().filter(|r| r.tool_hit).count();
+    let mean_fuel_consumed = if total_runs == 0 {
+        0.0
+    } else {
+        runs.iter().map(|r| r.fuel_consumed as f64).sum::<f64>() / total_runs as f64
+    };
+
+    BenchReport {
+        workload: workload_name.to_string(),
+        gateway_url: gateway_url.to_string(),
+        total_runs,
+        min_latency_ms: latencies.first().copied().unwrap_or(0.0),
+        median_latency_ms: percentile(&latencies, 0.50),
+        p95_latency_ms: percentile(&latencies, 0.95),
+        mean_fuel_consumed,
+        tool_hit_rate: if total_runs == 0 { 0.0 } else { hits as f64 / total_runs as f64 },
+        hallucination_rate: if total_runs == 0 { 0.0 } else { 1.0 - (hits as f64 / total_runs as f64) },
+        runs,
+    }
+}
+
+// Nearest-rank percentile over an already-sorted sample.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[rank]
+}
@@ -0,0 +1,21 @@
+mod bench;
+
+use anyhow::{bail, Result};
+
+// Entry point for `cargo xtask <subcommand>`. Keeps one-off dev/ops tooling (benchmarks,
+// later maybe fixture generation) out of the gateway binary itself.
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt().with_target(false).compact().init();
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        bail!("usage: cargo xtask <bench> [args...]");
+    }
+    let subcommand = args.remove(0);
+
+    match subcommand.as_str() {
+        "bench" => bench::run(bench::BenchArgs::parse(args)?).await,
+        other => bail!("unknown xtask subcommand '{}' (expected: bench)", other),
+    }
+}